@@ -0,0 +1,113 @@
+//! Decryption of the files encrypted with [age](https://age-encryption.org/v1), binary or
+//! armored, with the `age` crate.
+
+use std::io::Read;
+
+const MAGIC: &[u8] = b"age-encryption.org/v1\n";
+const ARMOR_BEGIN: &str = "-----BEGIN AGE ENCRYPTED FILE-----";
+
+/// An identity, i.e. a private key.
+pub(crate) type Identity = Box<dyn ::age::Identity + Send + Sync>;
+
+/// Parses the identities of an identity file, one per line, skipping the empty lines and the
+/// comments starting with `#`.
+///
+/// The error messages never contain the identities.
+pub(crate) fn parse_identities(contents: &str) -> Result<Vec<Identity>, String> {
+    let identities = ::age::IdentityFile::from_buffer(contents.as_bytes())
+        .map_err(|e| e.to_string())?
+        .into_identities()
+        .map_err(|e| e.to_string())?;
+    if identities.is_empty() {
+        return Err("no identity found".to_string());
+    }
+    Ok(identities)
+}
+
+/// Whether `bytes` are an age file, binary or armored.
+pub(crate) fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC) || bytes.trim_ascii_start().starts_with(ARMOR_BEGIN.as_bytes())
+}
+
+/// Decrypts the age file `bytes` with one of the `identities`.
+pub(crate) fn decrypt(bytes: &[u8], identities: &[Identity]) -> Result<Vec<u8>, String> {
+    let decryptor = ::age::Decryptor::new_buffered(::age::armor::ArmoredReader::new(bytes))
+        .map_err(|e| e.to_string())?;
+    let mut reader = decryptor
+        .decrypt(
+            identities
+                .iter()
+                .map(|id| id.as_ref() as &dyn ::age::Identity),
+        )
+        .map_err(|e| e.to_string())?;
+    let mut decrypted = Vec::new();
+    if let Err(e) = reader.read_to_end(&mut decrypted) {
+        crate::wipe::wipe(&mut decrypted);
+        return Err(e.to_string());
+    }
+    Ok(decrypted)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) const IDENTITY: &str =
+        "AGE-SECRET-KEY-1GZUGKMDJ58LZAJ7GE4AYTUCC854X5ZNUUAVY0YPT2H05S882Q9PS0G43E0";
+    const OTHER_IDENTITY: &str =
+        "AGE-SECRET-KEY-1YP98Z2HJJ8KSE4KAWHMFE4TPXYEEDUDLK3RSQYFZQHGRHAGJT3UQD2QKA7";
+
+    /// `hunter2\n`, encrypted for both identities.
+    pub(crate) const ENCRYPTED: &str = "-----BEGIN AGE ENCRYPTED FILE-----
+YWdlLWVuY3J5cHRpb24ub3JnL3YxCi0+IFgyNTUxOSBVN04zS3NpQXFqQ0t4TkVY
+RDNTK1BFU2VHMEdJdlV3YnlmRWZqZ1ZId21nCnYxM2dZRHpiSTB2NmFJaGVVcnFB
+cFIzcy9KTjUrcVZ2L1hNWTVQNW5Pa0kKLT4gWDI1NTE5IFM5MEd4WXVyTFF4aFdi
+OEZIdm1nS1Fta2l4MVEwb1BFaTFkczVCcWNIVDQKVEcyTVdOd3Fua3JRNWVvWjNu
+MjBlRTJhN0sxVFFoZlEyOE45ZVFUQXNNZwotLS0gcTVBNGZUVjNuWDd3eEt3aEJ0
+RVRtcTR5SDk5ZVBwOGNCNktrRXFJTUhhNAryEdfJG1Jg/ScmAHfr3pEpPUNYPz2b
+Ee3Nsz2N7bf5qi13m4w1k5L2
+-----END AGE ENCRYPTED FILE-----
+";
+
+    #[test]
+    fn decryption() {
+        assert!(is_encrypted(ENCRYPTED.as_bytes()));
+        let mut dearmored = Vec::new();
+        ::age::armor::ArmoredReader::new(ENCRYPTED.as_bytes())
+            .read_to_end(&mut dearmored)
+            .unwrap();
+        assert!(is_encrypted(&dearmored));
+        assert!(!is_encrypted(b"hunter2"));
+        for identity in [IDENTITY, OTHER_IDENTITY] {
+            let identities = parse_identities(&format!("# key\n\n{}\n", identity)).unwrap();
+            assert_eq!(decrypt(&dearmored, &identities).unwrap(), b"hunter2\n");
+            assert_eq!(
+                decrypt(ENCRYPTED.as_bytes(), &identities).unwrap(),
+                b"hunter2\n"
+            );
+        }
+        let identities = parse_identities(IDENTITY).unwrap();
+        let mut tampered = dearmored.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decrypt(&tampered, &identities).is_err());
+        let wrong = parse_identities(
+            "AGE-SECRET-KEY-1QQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQS3GV7Z4",
+        );
+        assert!(wrong.is_err() || decrypt(&dearmored, &wrong.unwrap()).is_err());
+    }
+
+    #[test]
+    fn identities() {
+        assert_eq!(
+            parse_identities("# nothing").err().unwrap(),
+            "no identity found"
+        );
+        // A flipped character breaks the checksum, and the error does not quote the key.
+        let corrupted = IDENTITY.replace("GZUG", "GZUH");
+        assert_eq!(
+            parse_identities(&corrupted).err().unwrap(),
+            "identity file contains non-identity data on line 1"
+        );
+        assert!(parse_identities(&IDENTITY.to_lowercase()).is_ok());
+    }
+}
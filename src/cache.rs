@@ -0,0 +1,88 @@
+//! In-memory cache of the reads, see [`FileEnv::cache_reads`](crate::FileEnv::cache_reads).
+
+use crate::wipe::wipe;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The contents read by the provider, by file or secret name, reused until they are older than
+/// the TTL. The contents are zeroized when they are removed.
+pub(crate) struct MemoryCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, Vec<u8>)>>,
+}
+
+impl std::fmt::Debug for MemoryCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryCache")
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MemoryCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<String, (Instant, Vec<u8>)>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The contents of `name`, if they were read less than the TTL ago.
+    pub(crate) fn get(&self, name: &str) -> Option<Vec<u8>> {
+        let mut entries = self.entries();
+        let (read_at, contents) = entries.get(name)?;
+        if read_at.elapsed() <= self.ttl {
+            return Some(contents.clone());
+        }
+        if let Some((_, mut contents)) = entries.remove(name) {
+            wipe(&mut contents);
+        }
+        None
+    }
+
+    /// Stores the `contents` of `name`, read now.
+    pub(crate) fn insert(&self, name: &str, contents: &[u8]) {
+        let previous = self
+            .entries()
+            .insert(name.to_string(), (Instant::now(), contents.to_vec()));
+        if let Some((_, mut contents)) = previous {
+            wipe(&mut contents);
+        }
+    }
+
+    /// Removes all the entries.
+    pub(crate) fn clear(&self) {
+        for (_, (_, mut contents)) in self.entries().drain() {
+            wipe(&mut contents);
+        }
+    }
+}
+
+impl Drop for MemoryCache {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_cache() {
+        let cache = MemoryCache::new(Duration::from_millis(50));
+        assert_eq!(cache.get("a"), None);
+        cache.insert("a", b"value");
+        assert_eq!(cache.get("a").as_deref(), Some(&b"value"[..]));
+        cache.clear();
+        assert_eq!(cache.get("a"), None);
+        cache.insert("a", b"value");
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(cache.get("a"), None);
+    }
+}
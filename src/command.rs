@@ -0,0 +1,118 @@
+//! Running the commands of the [command suffix](crate::FileEnv::with_cmd_suffix).
+
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// The env variables passed to the commands, in addition to the configured ones: the ones
+/// needed to find and run most programs.
+const KEPT_VARS: &[&str] = &["PATH", "HOME", "LANG", "TMPDIR", "SYSTEMROOT"];
+
+/// The configuration of the commands, see
+/// [`FileEnv::command_timeout`](crate::FileEnv::command_timeout).
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Options {
+    pub(crate) timeout: Option<Duration>,
+    /// The names of the env variables passed to the commands, besides [`KEPT_VARS`].
+    pub(crate) env: Vec<String>,
+}
+
+/// Runs `command` with the shell and returns its standard output.
+///
+/// The command runs with a scrubbed environment and no standard input, and its standard error
+/// is inherited. At most `max_size + 1` bytes are read, if given.
+pub(crate) fn run(
+    command: &str,
+    options: &Options,
+    max_size: Option<u64>,
+) -> Result<Vec<u8>, String> {
+    let mut shell = match cfg!(windows) {
+        true => {
+            let mut shell = Command::new("cmd");
+            shell.arg("/C");
+            shell
+        }
+        false => {
+            let mut shell = Command::new("sh");
+            shell.arg("-c");
+            shell
+        }
+    };
+    let kept = KEPT_VARS
+        .iter()
+        .copied()
+        .chain(options.env.iter().map(String::as_str))
+        .filter_map(|name| Some((name, std::env::var_os(name)?)));
+    let mut child = shell
+        .arg(command)
+        .env_clear()
+        .envs(kept)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| format!("could not run the shell: {}", e))?;
+    let mut stdout = child.stdout.take().expect("piped");
+    let mut reader = Some(std::thread::spawn(move || {
+        let mut output = Vec::new();
+        let read = match max_size {
+            Some(max) => (&mut stdout).take(max + 1).read_to_end(&mut output),
+            None => stdout.read_to_end(&mut output),
+        };
+        (read, output)
+    }));
+    let deadline = options.timeout.map(|timeout| Instant::now() + timeout);
+    let mut output = None;
+    let status = loop {
+        if reader.as_ref().is_some_and(|reader| reader.is_finished()) {
+            let (read, mut bytes) = reader
+                .take()
+                .expect("some")
+                .join()
+                .expect("reader panicked");
+            if let Some(max) = max_size.filter(|&max| bytes.len() as u64 > max) {
+                crate::wipe::wipe(&mut bytes);
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(too_large(max));
+            }
+            output = Some(read.map(|_| bytes));
+        }
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            break status;
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            let _ = child.kill();
+            let _ = child.wait();
+            if let Some(Ok(mut bytes)) = output {
+                crate::wipe::wipe(&mut bytes);
+            }
+            return Err(format!(
+                "timed out after {:?}",
+                options.timeout.unwrap_or_default()
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    };
+    let output = match (output, reader) {
+        (Some(output), _) => output,
+        (None, reader) => {
+            let (read, bytes) = reader.expect("some").join().expect("reader panicked");
+            read.map(|_| bytes)
+        }
+    };
+    let mut output = output.map_err(|e| format!("could not read its output: {}", e))?;
+    if let Some(max) = max_size.filter(|&max| output.len() as u64 > max) {
+        crate::wipe::wipe(&mut output);
+        return Err(too_large(max));
+    }
+    if !status.success() {
+        crate::wipe::wipe(&mut output);
+        return Err(format!("it failed with {}", status));
+    }
+    Ok(output)
+}
+
+fn too_large(max: u64) -> String {
+    format!("its output is larger than the limit of {} bytes", max)
+}
@@ -0,0 +1,52 @@
+//! Reading the values of the Consul KV store with its HTTP API, for `consul://kv/` references.
+//!
+//! The server and the token are configured as for the `consul` command-line tool, with
+//! `CONSUL_HTTP_ADDR` and `CONSUL_HTTP_TOKEN` or `CONSUL_HTTP_TOKEN_FILE`.
+
+/// The address of the local agent, used when `CONSUL_HTTP_ADDR` is not set.
+const DEFAULT_ADDR: &str = "http://127.0.0.1:8500";
+
+/// Reads the value of the `reference`, `kv/<key>`, with the [`http`](crate::http) options.
+pub(crate) fn read(
+    reference: &str,
+    http: &crate::http::Options,
+    max_size: Option<u64>,
+) -> std::io::Result<Vec<u8>> {
+    let key = reference.strip_prefix("kv/").ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "the Consul reference `{}` is not in the KV store, e.g. `consul://kv/{}`",
+                reference, reference
+            ),
+        )
+    })?;
+    let addr = std::env::var("CONSUL_HTTP_ADDR").unwrap_or_else(|_| DEFAULT_ADDR.to_string());
+    let addr = match addr.contains("://") {
+        true => addr,
+        false => format!("http://{}", addr),
+    };
+    let token = match std::env::var("CONSUL_HTTP_TOKEN") {
+        Ok(token) => Some(token),
+        Err(_) => match std::env::var_os("CONSUL_HTTP_TOKEN_FILE") {
+            Some(token_file) => Some(std::fs::read_to_string(&token_file).map_err(|e| {
+                std::io::Error::new(
+                    e.kind(),
+                    format!(
+                        "could not read the Consul token file `{}`: {}",
+                        token_file.to_string_lossy(),
+                        e
+                    ),
+                )
+            })?),
+            None => None,
+        },
+    };
+    let mut http = http.clone();
+    if let Some(token) = token {
+        http.headers
+            .push(("X-Consul-Token".to_string(), token.trim().to_string()));
+    }
+    let url = format!("{}/v1/kv/{}?raw", addr.trim_end_matches('/'), key);
+    crate::http::read(&url, &http, max_size)
+}
@@ -0,0 +1,56 @@
+//! The protocol of the Docker credential helpers, e.g. `docker-credential-pass`: the `get`
+//! command reads the server URL on stdin and prints the credentials as JSON on stdout.
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Gets the credentials of `server_url` from the helper `program`, and returns the `Secret` or,
+/// if `username`, the `Username`.
+pub(crate) fn get(program: &Path, server_url: &str, username: bool) -> Result<Vec<u8>, String> {
+    let mut child = Command::new(program)
+        .arg("get")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("could not run `{}`: {}", program.display(), e))?;
+    let mut stdin = child.stdin.take().expect("piped");
+    let written = stdin.write_all(server_url.as_bytes());
+    drop(stdin);
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    let response = crate::wipe::Zeroizing::new(String::from_utf8(output.stdout).map_err(|e| {
+        let mut bytes = e.into_bytes();
+        crate::wipe::wipe(&mut bytes);
+        format!("`{}` returned invalid UTF-8", program.display())
+    })?);
+    if !output.status.success() {
+        // The helpers print the error on stdout, e.g. `credentials not found in native keychain`.
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "`{}` could not get the credentials of `{}`: {}",
+            program.display(),
+            server_url,
+            if response.trim().is_empty() {
+                stderr.trim()
+            } else {
+                response.trim()
+            }
+        ));
+    }
+    written.map_err(|e| e.to_string())?;
+    let field = if username { "Username" } else { "Secret" };
+    serde_json::from_str::<figment::value::Value>(&response)
+        .map_err(|e| format!("invalid output from `{}`: {}", program.display(), e))?
+        .as_dict()
+        .and_then(|credentials| credentials.get(field))
+        .and_then(|value| value.as_str())
+        .map(|value| value.as_bytes().to_vec())
+        .ok_or_else(|| {
+            format!(
+                "invalid output from `{}`: no `{}` in the credentials",
+                program.display(),
+                field
+            )
+        })
+}
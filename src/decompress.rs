@@ -0,0 +1,102 @@
+//! Transparent decompression of file contents, detected from the magic bytes.
+//!
+//! The gzip contents are decompressed with `flate2`, and the zstd ones with `ruzstd`, without
+//! any external tool.
+
+use std::io::Read;
+
+/// The compression formats, detected from their magic bytes.
+#[derive(Clone, Copy)]
+enum Format {
+    #[cfg(feature = "gzip")]
+    Gzip,
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+/// Magic bytes of each supported format.
+const FORMATS: &[(&[u8], Format)] = &[
+    #[cfg(feature = "gzip")]
+    (&[0x1f, 0x8b], Format::Gzip),
+    #[cfg(feature = "zstd")]
+    (&[0x28, 0xb5, 0x2f, 0xfd], Format::Zstd),
+];
+
+/// Decompresses `bytes` if they are in a supported compressed format, otherwise returns them
+/// unchanged.
+///
+/// At most `max_size + 1` bytes of decompressed output are read, if given.
+pub(crate) fn decompress(mut bytes: Vec<u8>, max_size: Option<u64>) -> std::io::Result<Vec<u8>> {
+    let Some(&(_, format)) = FORMATS.iter().find(|(magic, _)| bytes.starts_with(magic)) else {
+        return Ok(bytes);
+    };
+    let limit = max_size.map_or(u64::MAX, |max| max + 1);
+    let mut output = Vec::new();
+    let result = match format {
+        #[cfg(feature = "gzip")]
+        Format::Gzip => flate2::read::MultiGzDecoder::new(bytes.as_slice())
+            .take(limit)
+            .read_to_end(&mut output)
+            .map(|_| ()),
+        #[cfg(feature = "zstd")]
+        Format::Zstd => read_zstd(&bytes, limit, &mut output),
+    };
+    crate::wipe::wipe(&mut bytes);
+    if let Err(e) = result {
+        crate::wipe::wipe(&mut output);
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("could not decompress the contents: {}", e),
+        ));
+    }
+    Ok(output)
+}
+
+/// Decompresses the zstd frames of `bytes` into `output`, up to `limit` bytes.
+#[cfg(feature = "zstd")]
+fn read_zstd(bytes: &[u8], limit: u64, output: &mut Vec<u8>) -> std::io::Result<()> {
+    let mut source = bytes;
+    while !source.is_empty() && (output.len() as u64) < limit {
+        let decoder = ruzstd::decoding::StreamingDecoder::new(&mut source)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        decoder
+            .take(limit - output.len() as u64)
+            .read_to_end(output)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd() {
+        // `bar`, compressed with zstd.
+        let compressed = [
+            40, 181, 47, 253, 4, 88, 25, 0, 0, 98, 97, 114, 89, 166, 39, 173,
+        ];
+        assert_eq!(decompress(compressed.to_vec(), None).unwrap(), b"bar");
+        let twice = [compressed, compressed].concat();
+        assert_eq!(decompress(twice.clone(), None).unwrap(), b"barbar");
+        assert_eq!(decompress(twice, Some(3)).unwrap(), b"barb");
+        assert!(decompress(compressed[..10].to_vec(), None).is_err());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip() {
+        // `bar`, compressed with gzip.
+        let compressed = [
+            31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 75, 74, 44, 2, 0, 170, 140, 255, 118, 3, 0, 0, 0,
+        ];
+        assert_eq!(decompress(compressed.to_vec(), Some(2)).unwrap(), b"bar");
+        assert!(decompress(compressed[..12].to_vec(), None).is_err());
+    }
+
+    #[test]
+    fn uncompressed() {
+        assert_eq!(decompress(b"bar".to_vec(), Some(1)).unwrap(), b"bar");
+    }
+}
@@ -0,0 +1,163 @@
+//! Encrypted on-disk cache of the values read from secret stores, see
+//! [`FileEnv::encrypted_cache`](crate::FileEnv::encrypted_cache), with the `encrypted-cache`
+//! feature.
+//!
+//! Each entry is a file named after the SHA-256 of the name of the secret, holding the time it
+//! was written, a random nonce, and the ChaCha20-Poly1305 encryption of the name and the value.
+
+use crate::wipe::wipe;
+use chacha20poly1305::aead::{Aead, Generate, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const MAGIC: &[u8] = b"figment-file-env-cache-v1\n";
+
+#[derive(Clone)]
+pub(crate) struct Cache {
+    pub(crate) dir: PathBuf,
+    pub(crate) key: [u8; 32],
+    pub(crate) ttl: Duration,
+}
+
+impl std::fmt::Debug for Cache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cache")
+            .field("dir", &self.dir)
+            .field("key", &"***")
+            .field("ttl", &self.ttl)
+            .finish()
+    }
+}
+
+impl Cache {
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(crate::sha256_hex(name.as_bytes()))
+    }
+
+    /// Stores the `value` of the secret `name`, replacing any previous entry.
+    pub(crate) fn store(&self, name: &str, value: &[u8]) -> std::io::Result<()> {
+        let mut plaintext = Vec::with_capacity(4 + name.len() + value.len());
+        plaintext.extend_from_slice(&(name.len() as u32).to_le_bytes());
+        plaintext.extend_from_slice(name.as_bytes());
+        plaintext.extend_from_slice(value);
+        let nonce = Nonce::generate();
+        let sealed = ChaCha20Poly1305::new(&self.key.into())
+            .encrypt(&nonce, plaintext.as_slice())
+            .expect("the cache entries are small");
+        wipe(&mut plaintext);
+        let mut entry = MAGIC.to_vec();
+        entry.extend_from_slice(&now().to_le_bytes());
+        entry.extend_from_slice(&nonce);
+        entry.extend_from_slice(&sealed);
+        std::fs::create_dir_all(&self.dir)?;
+        // Write to a temporary file and rename it, so that readers never see a partial entry.
+        let path = self.path(name);
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        write_private(&tmp, &entry)?;
+        std::fs::rename(&tmp, &path)
+    }
+
+    /// Loads the value of the secret `name`, if it is cached and not older than the TTL.
+    pub(crate) fn load(&self, name: &str) -> Option<Vec<u8>> {
+        let entry = std::fs::read(self.path(name)).ok()?;
+        let entry = entry.strip_prefix(MAGIC)?;
+        let (written, entry) = entry.split_at_checked(8)?;
+        let written = u64::from_le_bytes(written.try_into().expect("8 bytes"));
+        if now().saturating_sub(written) > self.ttl.as_secs() {
+            return None;
+        }
+        let (nonce, sealed) = entry.split_at_checked(12)?;
+        let nonce = Nonce::try_from(nonce).expect("12 bytes");
+        let mut plaintext = ChaCha20Poly1305::new(&self.key.into())
+            .decrypt(&nonce, sealed)
+            .ok()?;
+        let value = plaintext
+            .split_at_checked(4)
+            .and_then(|(len, rest)| {
+                let len = u32::from_le_bytes(len.try_into().expect("4 bytes")) as usize;
+                rest.split_at_checked(len)
+            })
+            .filter(|(stored_name, _)| *stored_name == name.as_bytes())
+            .map(|(_, value)| value.to_vec());
+        wipe(&mut plaintext);
+        value
+    }
+
+    /// Removes all the entries of the cache.
+    pub(crate) fn clear(&self) -> std::io::Result<()> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            entries => entries?,
+        };
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            // Only the entries, in case the directory is shared.
+            let is_entry = name.split('.').next().is_some_and(|hash| {
+                hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit())
+            });
+            if is_entry {
+                std::fs::remove_file(entry.path())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The current time, in seconds since the Unix epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Writes `contents` to the new file `path`, only accessible by its owner on Unix.
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    std::io::Write::write_all(&mut options.open(path)?, contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn store_and_load() {
+        let dir = std::env::temp_dir().join(format!("figment_cache_test_{}", std::process::id()));
+        let cache = Cache {
+            dir: dir.clone(),
+            key: [7; 32],
+            ttl: Duration::from_secs(60),
+        };
+        assert_eq!(cache.load("keyring:db"), None);
+        cache.store("keyring:db", b"hunter2").unwrap();
+        cache.store("keyring:other", b"abc").unwrap();
+        assert_eq!(cache.load("keyring:db").unwrap(), b"hunter2");
+        // Another key cannot decrypt the entry.
+        let other_key = Cache {
+            key: [8; 32],
+            ..cache.clone()
+        };
+        assert_eq!(other_key.load("keyring:db"), None);
+        // An entry moved to another name is rejected.
+        std::fs::copy(cache.path("keyring:db"), cache.path("keyring:other")).unwrap();
+        assert_eq!(cache.load("keyring:other"), None);
+        let expired = Cache {
+            ttl: Duration::ZERO,
+            ..cache.clone()
+        };
+        std::thread::sleep(Duration::from_millis(1100));
+        assert_eq!(expired.load("keyring:db"), None);
+        std::fs::write(dir.join("unrelated"), "").unwrap();
+        cache.clear().unwrap();
+        assert_eq!(cache.load("keyring:db"), None);
+        assert!(dir.join("unrelated").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
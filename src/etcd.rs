@@ -0,0 +1,63 @@
+//! Reading the values of etcd, with the `etcdctl` command-line tool, which must be available in
+//! the `PATH`. The cluster is configured as for `etcdctl`, e.g. with `ETCDCTL_ENDPOINTS`.
+
+use std::process::{Command, Stdio};
+
+/// Reads the value of `key`.
+pub(crate) fn read(key: &str) -> std::io::Result<Vec<u8>> {
+    use std::io::ErrorKind;
+    let error = |kind: ErrorKind, message: String| std::io::Error::new(kind, message);
+    // The JSON output tells a missing key from an empty value, unlike `--print-value-only`.
+    let output = Command::new("etcdctl")
+        .args(["get", "--write-out", "json", "--"])
+        .arg(key)
+        .env("ETCDCTL_API", "3")
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| error(e.kind(), format!("could not run `etcdctl`: {}", e)))?;
+    let response = crate::wipe::Zeroizing::new(String::from_utf8(output.stdout).map_err(|e| {
+        let mut bytes = e.into_bytes();
+        crate::wipe::wipe(&mut bytes);
+        error(
+            ErrorKind::InvalidData,
+            "`etcdctl` returned invalid UTF-8".to_string(),
+        )
+    })?);
+    if !output.status.success() {
+        return Err(error(
+            ErrorKind::Other,
+            format!(
+                "`etcdctl` could not read the key `{}`: {}",
+                key,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        ));
+    }
+    let response = serde_json::from_str::<figment::value::Value>(&response).map_err(|e| {
+        error(
+            ErrorKind::InvalidData,
+            format!("invalid output from `etcdctl`: {}", e),
+        )
+    })?;
+    let value = response
+        .as_dict()
+        .and_then(|response| response.get("kvs"))
+        .and_then(|kvs| kvs.as_array())
+        .and_then(|kvs| kvs.first())
+        .and_then(|kv| kv.as_dict())
+        .map(|kv| kv.get("value").and_then(|value| value.as_str()));
+    match value {
+        None => Err(error(
+            ErrorKind::NotFound,
+            format!("the etcd key `{}` does not exist", key),
+        )),
+        // An empty value is omitted from the output.
+        Some(None) => Ok(Vec::new()),
+        Some(Some(value)) => crate::base64::decode(value.trim_end_matches('=')).ok_or_else(|| {
+            error(
+                ErrorKind::InvalidData,
+                "invalid output from `etcdctl`: the value is not base64".to_string(),
+            )
+        }),
+    }
+}
@@ -0,0 +1,114 @@
+//! The [`FileTree`] provider, mapping a directory tree to nested config values.
+
+use crate::wipe::Zeroizing;
+use figment::{
+    error::Kind,
+    value::{Dict, Value},
+    Metadata, Profile, Provider, Source,
+};
+use std::path::{Path, PathBuf};
+
+/// Provider reading a directory tree: each file is a key holding its contents, and each
+/// subdirectory is a nested dict.
+///
+/// This is the layout of Kubernetes projected volumes and confd-style directories. The names
+/// starting with a `.` are skipped, along with the `..data` links of Kubernetes. Symbolic links
+/// are followed.
+///
+/// ```rust
+/// # use serde::Deserialize;
+/// # use figment::Figment;
+/// # use figment_file_env_provider::FileTree;
+/// #
+/// # #[derive(Deserialize)]
+/// # struct Database {
+/// #   user: String,
+/// #   port: u16,
+/// # }
+/// # #[derive(Deserialize)]
+/// # struct Config {
+/// #   database: Database,
+/// # }
+/// #
+/// # figment::Jail::expect_with(|jail| {
+/// # jail.create_dir("conf.d/database")?;
+/// # jail.create_file("conf.d/database/user", "admin")?;
+/// # jail.create_file("conf.d/database/port", "5432\n")?;
+/// // Files: `./conf.d/database/user` containing `admin`, `./conf.d/database/port` containing
+/// // `5432`
+/// let config: Config = Figment::new()
+///     .merge(FileTree::new("conf.d"))
+///     .extract()?;
+/// assert_eq!(config.database.user, "admin");
+/// assert_eq!(config.database.port, 5432);
+/// # Ok(())
+/// # });
+/// ```
+#[derive(Clone, Debug)]
+pub struct FileTree {
+    root: PathBuf,
+    profile: Profile,
+}
+
+impl FileTree {
+    /// Build from the directory `root`, which must exist when extracting.
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self {
+            root: root.into(),
+            profile: Profile::Default,
+        }
+    }
+
+    /// Emit the values in the given profile (the default profile by default).
+    pub fn profile<P: Into<Profile>>(self, profile: P) -> Self {
+        Self {
+            profile: profile.into(),
+            ..self
+        }
+    }
+}
+
+impl Provider for FileTree {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("file tree").source(Source::File(self.root.clone()))
+    }
+
+    fn data(&self) -> Result<figment::value::Map<Profile, Dict>, figment::Error> {
+        Ok(self.profile.collect(read_tree(&self.root)?))
+    }
+}
+
+/// Reads the directory `dir` recursively into a dict.
+// Bubbles up to `Provider::data`.
+#[allow(clippy::result_large_err)]
+fn read_tree(dir: &Path) -> Result<Dict, figment::Error> {
+    let error = |path: &Path, e: std::io::Error| {
+        figment::Error::from(Kind::Message(format!(
+            "Could not read `{}`: {:#}",
+            path.display(),
+            e
+        )))
+    };
+    let mut dict = Dict::new();
+    for entry in std::fs::read_dir(dir).map_err(|e| error(dir, e))? {
+        let path = entry.map_err(|e| error(dir, e))?.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if name.starts_with('.') {
+            continue;
+        }
+        let name = name.to_string();
+        let metadata = std::fs::metadata(&path).map_err(|e| error(&path, e))?;
+        let value = if metadata.is_dir() {
+            read_tree(&path)?.into()
+        } else {
+            let contents = std::fs::read_to_string(&path).map_err(|e| error(&path, e))?;
+            Zeroizing::new(contents)
+                .parse::<Value>()
+                .expect("infallible")
+        };
+        dict.insert(name, value);
+    }
+    Ok(dict)
+}
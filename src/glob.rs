@@ -0,0 +1,143 @@
+//! Minimal glob expansion for file paths, supporting `*`, `?` and `[...]` classes.
+
+use std::path::{Component, Path, PathBuf};
+
+/// Whether `s` contains any glob metacharacter.
+///
+/// The `?` of the Windows verbatim (`\\?\`) and device (`\\.\`) prefixes is not a wildcard.
+pub(crate) fn is_pattern(s: &str) -> bool {
+    let s = ["\\\\?\\", "\\\\.\\", "//?/", "//./"]
+        .iter()
+        .find_map(|prefix| s.strip_prefix(prefix))
+        .unwrap_or(s);
+    s.contains(['*', '?', '['])
+}
+
+/// Returns all the files matching `pattern`, sorted.
+///
+/// Wildcards never match a path separator, and only match names starting with a `.` if the
+/// pattern component starts with a `.` as well.
+pub(crate) fn expand(pattern: &str) -> std::io::Result<Vec<PathBuf>> {
+    let mut candidates = vec![PathBuf::new()];
+    for component in Path::new(pattern).components() {
+        let name = match component {
+            Component::Normal(name) => name.to_string_lossy(),
+            other => {
+                candidates.iter_mut().for_each(|c| c.push(other));
+                continue;
+            }
+        };
+        if !is_pattern(&name) {
+            candidates.iter_mut().for_each(|c| c.push(&*name));
+            continue;
+        }
+        let pattern: Vec<char> = name.chars().collect();
+        let mut next = Vec::new();
+        for candidate in &candidates {
+            let dir = if candidate.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                candidate.as_path()
+            };
+            let entries = match std::fs::read_dir(dir) {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) if e.kind() == std::io::ErrorKind::NotADirectory => continue,
+                Err(e) => return Err(e),
+            };
+            for entry in entries {
+                let file_name = entry?.file_name();
+                let file_name = file_name.to_string_lossy();
+                if file_name.starts_with('.') && pattern.first() != Some(&'.') {
+                    continue;
+                }
+                let file_name: Vec<char> = file_name.chars().collect();
+                if matches(&pattern, &file_name) {
+                    next.push(candidate.join(file_name.iter().collect::<String>()));
+                }
+            }
+        }
+        candidates = next;
+    }
+    let mut files: Vec<PathBuf> = candidates.into_iter().filter(|p| p.is_file()).collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Whether `name` matches the glob `pattern`, e.g. `*_cert` for config keys.
+pub(crate) fn matches_str(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    matches(&pattern, &name)
+}
+
+/// Whether the `name` matches the glob `pattern` (a single path component).
+fn matches(pattern: &[char], name: &[char]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len()).any(|skip| matches(&pattern[1..], &name[skip..])),
+        Some('?') => !name.is_empty() && matches(&pattern[1..], &name[1..]),
+        Some('[') => {
+            let Some((class_len, matched)) = name.first().and_then(|c| match_class(pattern, *c))
+            else {
+                return false;
+            };
+            matched && matches(&pattern[class_len..], &name[1..])
+        }
+        Some(c) => name.first() == Some(c) && matches(&pattern[1..], &name[1..]),
+    }
+}
+
+/// Matches `c` against the class at the start of `pattern` (e.g. `[a-z]` or `[!abc]`).
+///
+/// Returns the length of the class in the pattern and whether it matched, or `None` if the class
+/// is not terminated.
+fn match_class(pattern: &[char], c: char) -> Option<(usize, bool)> {
+    let mut i = 1;
+    let negated = matches!(pattern.get(i), Some('!') | Some('^'));
+    if negated {
+        i += 1;
+    }
+    let mut matched = false;
+    let mut first = true;
+    while let Some(&start) = pattern.get(i) {
+        if start == ']' && !first {
+            return Some((i + 1, matched != negated));
+        }
+        first = false;
+        if pattern.get(i + 1) == Some(&'-') && pattern.get(i + 2).is_some_and(|&e| e != ']') {
+            matched |= (start..=pattern[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            matched |= start == c;
+            i += 1;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matching() {
+        assert!(matches_str("*.pem", "ca.pem"));
+        assert!(matches_str("*", ""));
+        assert!(!matches_str("*.pem", "ca.pem.bak"));
+        assert!(matches_str("ca?.pem", "ca1.pem"));
+        assert!(!matches_str("ca?.pem", "ca.pem"));
+        assert!(matches_str("ca[0-9].pem", "ca7.pem"));
+        assert!(!matches_str("ca[!0-9].pem", "ca7.pem"));
+        assert!(matches_str("ca[]].pem", "ca].pem"));
+        assert!(!matches_str("ca[0-9.pem", "ca7.pem"));
+    }
+
+    #[test]
+    fn windows_prefixes() {
+        assert!(!is_pattern(r"\\?\C:\secrets\foo"));
+        assert!(!is_pattern(r"\\.\pipe\secrets"));
+        assert!(is_pattern(r"\\?\C:\secrets\*.pem"));
+        assert!(is_pattern(r"C:\secrets\foo?"));
+    }
+}
@@ -0,0 +1,140 @@
+//! Fetching the values served over HTTP(S), with the `ureq` client.
+//!
+//! The client is shared by the `http`, `vault` and `consul` features, but only the `http`
+//! feature fetches the `http://` and `https://` URLs of the env variables.
+
+use std::io::{ErrorKind, Read};
+
+/// The configuration of the requests, see [`FileEnv::http_header`](crate::FileEnv::http_header).
+#[derive(Clone, Default)]
+pub(crate) struct Options {
+    pub(crate) headers: Vec<(String, String)>,
+    pub(crate) timeout: Option<std::time::Duration>,
+}
+
+impl std::fmt::Debug for Options {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The header values often hold credentials.
+        let headers: Vec<_> = self.headers.iter().map(|(name, _)| (name, "***")).collect();
+        f.debug_struct("Options")
+            .field("headers", &headers)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+/// Fetches the body of `url` with a `GET` request, failing on a status other than a success,
+/// including the redirections, which could send the headers to another server.
+///
+/// A timeout gives an error of kind [`ErrorKind::TimedOut`], which can be retried. At most
+/// `max_size + 1` bytes are read, if given.
+pub(crate) fn read(
+    url: &str,
+    options: &Options,
+    max_size: Option<u64>,
+) -> std::io::Result<Vec<u8>> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(options.timeout)
+        .max_redirects(0)
+        .http_status_as_error(false)
+        .build()
+        .into();
+    let mut request = agent.get(url);
+    for (name, value) in &options.headers {
+        request = request.header(name, value);
+    }
+    let response = request.call().map_err(to_io_error)?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(std::io::Error::other(format!(
+            "the server returned the HTTP status {}",
+            status
+        )));
+    }
+    let limit = max_size.map_or(u64::MAX, |max| max + 1);
+    let mut output = Vec::new();
+    let result = response
+        .into_body()
+        .into_reader()
+        .take(limit)
+        .read_to_end(&mut output);
+    if let Err(e) = result {
+        crate::wipe::wipe(&mut output);
+        // The errors of the client are wrapped, e.g. a timeout while reading the body.
+        let timed_out = e
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<ureq::Error>())
+            .is_some_and(|e| matches!(e, ureq::Error::Timeout(_)));
+        return Err(match timed_out {
+            true => std::io::Error::new(ErrorKind::TimedOut, e),
+            false => e,
+        });
+    }
+    Ok(output)
+}
+
+/// Converts the error `e` of a request, keeping the timeouts apart for the retries.
+fn to_io_error(e: ureq::Error) -> std::io::Error {
+    match e {
+        ureq::Error::Io(e) => e,
+        ureq::Error::Timeout(_) => std::io::Error::new(
+            ErrorKind::TimedOut,
+            format!("could not fetch the URL: {}", e),
+        ),
+        e => std::io::Error::other(format!("could not fetch the URL: {}", e)),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// Serves the HTTP requests on a local port until the end of the tests: `respond` gets the
+    /// request line and headers, and returns the status and the body. Returns the URL of the
+    /// server, e.g. `http://127.0.0.1:1234`.
+    pub(crate) fn serve(respond: impl Fn(&str) -> (u16, String) + Send + 'static) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = Vec::new();
+                let mut buffer = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    match stream.read(&mut buffer) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => request.extend_from_slice(&buffer[..n]),
+                    }
+                }
+                let (status, body) = respond(&String::from_utf8_lossy(&request));
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {} Status\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn read_limits() {
+        let url = serve(|request| match request.starts_with("GET /slow ") {
+            true => {
+                std::thread::sleep(std::time::Duration::from_millis(500));
+                (200, "late".to_string())
+            }
+            false => (200, "0123456789".to_string()),
+        });
+        let options = Options {
+            headers: Vec::new(),
+            timeout: Some(std::time::Duration::from_millis(100)),
+        };
+        let read = |path: &str, max_size| read(&format!("{}{}", url, path), &options, max_size);
+        assert_eq!(read("/", None).unwrap(), b"0123456789");
+        assert_eq!(read("/", Some(4)).unwrap(), b"01234");
+        assert_eq!(read("/slow", None).unwrap_err().kind(), ErrorKind::TimedOut);
+    }
+}
@@ -0,0 +1,270 @@
+//! Discovery of the config keys used by a type, from its `Deserialize` implementation, see
+//! [`FileEnv::only_used_by`](crate::FileEnv::only_used_by).
+//!
+//! The type is deserialized from a tracing deserializer giving placeholder values: the fields of
+//! the structs are recorded, and refined when they are structs themselves. If the deserialization
+//! fails part of the way, e.g. because a custom implementation rejects a placeholder value, the
+//! fields not visited are kept whole: the keys are always a superset of the used ones.
+
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use std::cell::RefCell;
+
+/// The dotted paths of the fields used by `T`. An empty path means that all the keys under the
+/// parent are used, e.g. for a map or a flattened struct.
+pub(crate) fn used_keys<T: DeserializeOwned>() -> Vec<String> {
+    let keys = RefCell::new(vec![String::new()]);
+    let _ = T::deserialize(Tracer {
+        path: String::new(),
+        keys: &keys,
+    });
+    keys.into_inner()
+}
+
+/// Whether the `config_key` may be used according to the `used_keys`: it is a used key, a parent
+/// of one or a child of one.
+pub(crate) fn is_used(used_keys: &[String], config_key: &str) -> bool {
+    let is_prefix = |prefix: &str, key: &str| {
+        prefix.is_empty()
+            || key.len() >= prefix.len()
+                && key[..prefix.len()].eq_ignore_ascii_case(prefix)
+                && key[prefix.len()..].chars().next().is_none_or(|c| c == '.')
+    };
+    used_keys
+        .iter()
+        .any(|used| is_prefix(used, config_key) || is_prefix(config_key, used))
+}
+
+#[derive(Debug)]
+struct Error;
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("introspection")
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: std::fmt::Display>(_: T) -> Self {
+        Error
+    }
+}
+
+struct Tracer<'a> {
+    path: String,
+    keys: &'a RefCell<Vec<String>>,
+}
+
+impl Tracer<'_> {
+    fn child(&self, field: &str) -> String {
+        match self.path.is_empty() {
+            true => field.to_string(),
+            false => format!("{}.{}", self.path, field),
+        }
+    }
+}
+
+macro_rules! placeholder {
+    ($($method:ident => $visit:ident($($value:expr)?),)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                visitor.$visit($($value)?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Tracer<'_> {
+    type Error = Error;
+
+    placeholder! {
+        deserialize_any => visit_unit(),
+        deserialize_bool => visit_bool(false),
+        deserialize_i8 => visit_i8(0),
+        deserialize_i16 => visit_i16(0),
+        deserialize_i32 => visit_i32(0),
+        deserialize_i64 => visit_i64(0),
+        deserialize_i128 => visit_i128(0),
+        deserialize_u8 => visit_u8(0),
+        deserialize_u16 => visit_u16(0),
+        deserialize_u32 => visit_u32(0),
+        deserialize_u64 => visit_u64(0),
+        deserialize_u128 => visit_u128(0),
+        deserialize_f32 => visit_f32(0.0),
+        deserialize_f64 => visit_f64(0.0),
+        deserialize_char => visit_char('0'),
+        deserialize_str => visit_str(""),
+        deserialize_string => visit_string(String::new()),
+        deserialize_bytes => visit_bytes(&[]),
+        deserialize_byte_buf => visit_byte_buf(Vec::new()),
+        deserialize_unit => visit_unit(),
+        deserialize_identifier => visit_str(""),
+        deserialize_ignored_any => visit_unit(),
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(de::value::SeqDeserializer::new(std::iter::empty::<()>()))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        // All the keys under the path may be used: keep it whole.
+        visitor.visit_map(de::value::MapDeserializer::new(
+            std::iter::empty::<((), ())>(),
+        ))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        {
+            // Refine the path into its fields.
+            let mut keys = self.keys.borrow_mut();
+            keys.retain(|key| *key != self.path);
+            keys.extend(fields.iter().map(|field| self.child(field)));
+        }
+        visitor.visit_map(Fields {
+            tracer: self,
+            fields: fields.iter(),
+            current: None,
+        })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Error> {
+        Err(Error)
+    }
+}
+
+/// The fields of a struct, each with a placeholder value.
+struct Fields<'a> {
+    tracer: Tracer<'a>,
+    fields: std::slice::Iter<'static, &'static str>,
+    current: Option<&'static str>,
+}
+
+impl<'de> de::MapAccess<'de> for Fields<'_> {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        let Some(field) = self.fields.next() else {
+            return Ok(None);
+        };
+        self.current = Some(field);
+        seed.deserialize(field.into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let field = self.current.take().ok_or(Error)?;
+        seed.deserialize(Tracer {
+            path: self.tracer.child(field),
+            keys: self.tracer.keys,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(serde::Deserialize)]
+    struct Database {
+        url: String,
+        password: Option<String>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(serde::Deserialize)]
+    struct Config {
+        port: u16,
+        db: Database,
+        peers: Vec<String>,
+        extra: std::collections::HashMap<String, String>,
+        #[serde(rename = "apiToken")]
+        api_token: String,
+        mode: Mode,
+    }
+
+    #[allow(dead_code)]
+    #[derive(serde::Deserialize)]
+    enum Mode {
+        Fast,
+    }
+
+    #[test]
+    fn keys() {
+        let mut keys = used_keys::<Config>();
+        keys.sort();
+        assert_eq!(
+            keys,
+            [
+                "apiToken",
+                "db.password",
+                "db.url",
+                "extra",
+                "mode",
+                "peers",
+                "port"
+            ]
+        );
+        assert!(is_used(&keys, "db.url"));
+        assert!(is_used(&keys, "db"));
+        assert!(is_used(&keys, "extra.anything"));
+        assert!(is_used(&keys, "apitoken"));
+        assert!(!is_used(&keys, "db.user"));
+        assert!(!is_used(&keys, "unused"));
+        assert!(!is_used(&keys, "ports"));
+        assert!(is_used(
+            &used_keys::<std::collections::HashMap<String, u64>>(),
+            "any"
+        ));
+    }
+}
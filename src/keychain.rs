@@ -0,0 +1,38 @@
+//! Reading the passwords stored in the macOS Keychain, with the `security` command-line tool.
+
+use std::process::{Command, Stdio};
+
+/// Reads the generic password of `name`, `<service>/<account>` or just `<service>`.
+pub(crate) fn read(name: &str) -> std::io::Result<Vec<u8>> {
+    let (service, account) = match name.split_once('/') {
+        Some((service, account)) => (service, Some(account)),
+        None => (name, None),
+    };
+    let mut command = Command::new("security");
+    command.args(["find-generic-password", "-s", service]);
+    if let Some(account) = account {
+        command.args(["-a", account]);
+    }
+    let output = command
+        .arg("-w")
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| std::io::Error::new(e.kind(), format!("could not run `security`: {}", e)))?;
+    let mut password = output.stdout;
+    if !output.status.success() {
+        crate::wipe::wipe(&mut password);
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!(
+                "`security` could not find the password `{}`: {}",
+                name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        ));
+    }
+    // `security` ends the password with a newline.
+    if password.last() == Some(&b'\n') {
+        password.pop();
+    }
+    Ok(password)
+}
@@ -0,0 +1,48 @@
+//! Reading the secrets of 1Password, with the `op` command-line tool, which must be available
+//! in the `PATH`. The tool also reads from a 1Password Connect server when `OP_CONNECT_HOST`
+//! and `OP_CONNECT_TOKEN` are set.
+
+use std::io::Read;
+use std::process::{Command, Stdio};
+
+/// Reads the secret `reference`, e.g. `op://vault/item/field`.
+///
+/// At most `max_size + 1` bytes are read, if given.
+pub(crate) fn read(reference: &str, max_size: Option<u64>) -> std::io::Result<Vec<u8>> {
+    let mut child = Command::new("op")
+        .args(["read", "--no-newline", "--"])
+        .arg(reference)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| std::io::Error::new(e.kind(), format!("could not run `op`: {}", e)))?;
+    let mut output = Vec::new();
+    let mut stdout = child.stdout.take().expect("piped");
+    match max_size {
+        Some(max) => (&mut stdout).take(max + 1).read_to_end(&mut output)?,
+        None => stdout.read_to_end(&mut output)?,
+    };
+    let too_large = max_size.is_some_and(|max| output.len() as u64 > max);
+    if too_large {
+        // The caller reports the error, no need to read the rest.
+        let _ = child.kill();
+    }
+    drop(stdout);
+    let status = child.wait_with_output()?;
+    if too_large {
+        return Ok(output);
+    }
+    if !status.status.success() {
+        crate::wipe::wipe(&mut output);
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!(
+                "`op` could not read `{}`: {}",
+                reference,
+                String::from_utf8_lossy(&status.stderr).trim()
+            ),
+        ));
+    }
+    Ok(output)
+}
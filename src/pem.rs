@@ -0,0 +1,98 @@
+//! Syntactic validation of PEM-encoded values (RFC 7468).
+
+/// Checks that `contents` is a sequence of one or more well-formed PEM blocks, ignoring text
+/// outside of the blocks.
+///
+/// Returns a description of the problem otherwise, quoting the contents unless `redact`.
+pub(crate) fn validate(contents: &str, redact: bool) -> Result<(), String> {
+    let show = |fragment: &str| match redact {
+        true => "***".to_string(),
+        false => fragment.to_string(),
+    };
+    let mut blocks = 0;
+    let mut lines = contents.lines().map(str::trim);
+    while let Some(line) = lines.next() {
+        let Some(label) = line
+            .strip_prefix("-----BEGIN ")
+            .and_then(|l| l.strip_suffix("-----"))
+        else {
+            if line.starts_with("-----END ") {
+                return Err(format!("unexpected `{}`", show(line)));
+            }
+            continue;
+        };
+        let end = format!("-----END {}-----", label);
+        let mut body_len = 0;
+        loop {
+            match lines.next() {
+                None => return Err(format!("missing `{}`", show(&end))),
+                Some(line) if line == end => break,
+                Some(line) if line.starts_with("-----") => {
+                    return Err(format!("expected `{}`, found `{}`", show(&end), show(line)))
+                }
+                // Encapsulated headers, e.g. for encrypted legacy keys.
+                Some(line) if line.contains(':') || line.is_empty() => {}
+                Some(line) => {
+                    if let Some(c) = line
+                        .chars()
+                        .find(|c| !c.is_ascii_alphanumeric() && !matches!(c, '+' | '/' | '='))
+                    {
+                        return Err(format!(
+                            "invalid character `{}` in the `{}` block",
+                            show(&c.to_string()),
+                            show(label)
+                        ));
+                    }
+                    body_len += line.len();
+                }
+            }
+        }
+        if body_len == 0 || body_len % 4 != 0 {
+            return Err(format!(
+                "invalid base64 length in the `{}` block",
+                show(label)
+            ));
+        }
+        blocks += 1;
+    }
+    if blocks == 0 {
+        return Err("no PEM block found".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validation() {
+        let cert = "-----BEGIN CERTIFICATE-----\nMIIB\nAA==\n-----END CERTIFICATE-----\n";
+        assert!(validate(cert, false).is_ok());
+        assert!(validate(&format!("subject=foo\n{}{}", cert, cert), false).is_ok());
+        assert!(validate("not a cert", false).is_err());
+        assert!(validate("-----BEGIN CERTIFICATE-----\nMIIB\n", false).is_err());
+        assert!(validate(
+            "-----BEGIN CERTIFICATE-----\nMI*B\n-----END CERTIFICATE-----",
+            false
+        )
+        .is_err());
+        assert!(validate(
+            "-----BEGIN CERTIFICATE-----\nMIB\n-----END CERTIFICATE-----",
+            false
+        )
+        .is_err());
+        assert!(validate(
+            "-----BEGIN CERTIFICATE-----\nMIIB\n-----END PRIVATE KEY-----",
+            false
+        )
+        .is_err());
+        assert_eq!(
+            validate(
+                "-----BEGIN CERTIFICATE-----\nMI*B\n-----END CERTIFICATE-----",
+                true
+            ),
+            Err("invalid character `***` in the `***` block".to_string())
+        );
+    }
+}
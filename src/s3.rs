@@ -0,0 +1,67 @@
+//! Reading the objects of S3 (or a compatible object storage), with the `aws` command-line tool,
+//! which must be available in the `PATH`. The credentials are configured as for the tool, e.g.
+//! with `AWS_PROFILE` or an instance role.
+
+use std::io::Read;
+use std::process::{Command, Stdio};
+
+/// The configuration of the requests, see [`FileEnv::s3_region`](crate::FileEnv::s3_region).
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Options {
+    pub(crate) region: Option<String>,
+    /// The URL of a compatible object storage, e.g. MinIO.
+    pub(crate) endpoint: Option<String>,
+}
+
+/// Reads the object `url`, `s3://<bucket>/<key>`.
+///
+/// At most `max_size + 1` bytes are read, if given.
+pub(crate) fn read(
+    url: &str,
+    options: &Options,
+    max_size: Option<u64>,
+) -> std::io::Result<Vec<u8>> {
+    let mut command = Command::new("aws");
+    command.args(["s3", "cp", "--only-show-errors"]);
+    if let Some(region) = &options.region {
+        command.args(["--region", region]);
+    }
+    if let Some(endpoint) = &options.endpoint {
+        command.args(["--endpoint-url", endpoint]);
+    }
+    let mut child = command
+        .args([url, "-"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| std::io::Error::new(e.kind(), format!("could not run `aws`: {}", e)))?;
+    let mut output = Vec::new();
+    let mut stdout = child.stdout.take().expect("piped");
+    match max_size {
+        Some(max) => (&mut stdout).take(max + 1).read_to_end(&mut output)?,
+        None => stdout.read_to_end(&mut output)?,
+    };
+    let too_large = max_size.is_some_and(|max| output.len() as u64 > max);
+    if too_large {
+        // The caller reports the error, no need to download the rest.
+        let _ = child.kill();
+    }
+    drop(stdout);
+    let status = child.wait_with_output()?;
+    if too_large {
+        return Ok(output);
+    }
+    if !status.status.success() {
+        crate::wipe::wipe(&mut output);
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!(
+                "`aws` could not download `{}`: {}",
+                url,
+                String::from_utf8_lossy(&status.stderr).trim()
+            ),
+        ));
+    }
+    Ok(output)
+}
@@ -0,0 +1,132 @@
+//! The [`SecretsDir`] provider, reading a flat directory of files as keys.
+
+use crate::FileEnv;
+use figment::{
+    error::Kind,
+    providers::Env,
+    value::{Dict, Uncased},
+    Metadata, Profile, Provider, Source,
+};
+use std::path::PathBuf;
+
+/// Provider reading every file of a flat directory as a key holding its contents, like
+/// daemontools' envdir or Docker's `/run/secrets`.
+///
+/// The file names are processed like the env variables of a [`FileEnv`]: they are lowercased
+/// (after removing the [prefix](SecretsDir::prefixed), if any), and the
+/// [options](SecretsDir::configure) of [`FileEnv`] apply to them, e.g. [`FileEnv::line_lists`]
+/// or [`FileEnv::required`]. The names starting with a `.` and the subdirectories are skipped.
+///
+/// ```rust
+/// # use serde::Deserialize;
+/// # use figment::Figment;
+/// # use figment_file_env_provider::SecretsDir;
+/// #
+/// # #[derive(Deserialize)]
+/// # struct Config {
+/// #   db_password: String,
+/// # }
+/// #
+/// # figment::Jail::expect_with(|jail| {
+/// # jail.create_dir("secrets")?;
+/// # jail.create_file("secrets/db_password", "hunter2")?;
+/// // Contents of `./secrets/db_password`: `hunter2`
+/// let config: Config = Figment::new()
+///     .merge(SecretsDir::new("secrets"))
+///     .extract()?;
+/// assert_eq!(config.db_password, "hunter2");
+/// # Ok(())
+/// # });
+/// ```
+#[derive(Clone, Debug)]
+pub struct SecretsDir {
+    dir: PathBuf,
+    prefix: Option<String>,
+    file_env: FileEnv,
+}
+
+impl SecretsDir {
+    /// Build from the directory `dir`, which must exist when extracting.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self {
+            dir: dir.into(),
+            prefix: None,
+            // The env is only used for its profile: the variables come from the directory.
+            file_env: FileEnv::files_only(Env::raw().only(&[])),
+        }
+    }
+
+    /// Only read the files whose name starts with `prefix` (case-insensitively), and remove the
+    /// prefix from the keys.
+    ///
+    /// ```rust
+    /// use figment_file_env_provider::SecretsDir;
+    /// // This provider will read `/run/secrets/APP_DB_PASSWORD` as `db_password`, and skip
+    /// // `/run/secrets/OTHER_APP_KEY`.
+    /// let secrets = SecretsDir::new("/run/secrets").prefixed("APP_");
+    /// ```
+    pub fn prefixed(self, prefix: &str) -> Self {
+        Self {
+            prefix: Some(prefix.to_string()),
+            ..self
+        }
+    }
+
+    /// Apply the options of [`FileEnv`] to the files of the directory.
+    ///
+    /// The file markers are ignored, as every file holds a value.
+    ///
+    /// ```rust
+    /// use figment_file_env_provider::{EmptyFilePolicy, SecretsDir};
+    /// let secrets = SecretsDir::new("/run/secrets").configure(|file_env| {
+    ///     file_env
+    ///         .empty_files(EmptyFilePolicy::Error)
+    ///         .max_file_size(64 * 1024)
+    /// });
+    /// ```
+    pub fn configure<F: FnOnce(FileEnv) -> FileEnv>(self, configure: F) -> Self {
+        Self {
+            file_env: configure(self.file_env),
+            ..self
+        }
+    }
+
+    /// Emit the values in the given profile (the default profile by default).
+    pub fn profile<P: Into<Profile>>(self, profile: P) -> Self {
+        self.configure(|file_env| file_env.profile(profile))
+    }
+}
+
+impl Provider for SecretsDir {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("secrets directory").source(Source::File(self.dir.clone()))
+    }
+
+    fn data(&self) -> Result<figment::value::Map<Profile, Dict>, figment::Error> {
+        let error = |e: std::io::Error| {
+            Kind::Message(format!("Could not read `{}`: {:#}", self.dir.display(), e))
+        };
+        let mut vars = Vec::new();
+        for entry in std::fs::read_dir(&self.dir).map_err(error)? {
+            let path = entry.map_err(error)?.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let key = match &self.prefix {
+                Some(prefix) => match name.get(..prefix.len()) {
+                    Some(start) if start.eq_ignore_ascii_case(prefix) => &name[prefix.len()..],
+                    _ => continue,
+                },
+                None => name,
+            };
+            if key.is_empty() || name.starts_with('.') || !path.is_file() {
+                continue;
+            }
+            vars.push((
+                Uncased::from(key.to_ascii_lowercase()),
+                path.to_string_lossy().into_owned(),
+            ));
+        }
+        self.file_env.data_from(vars)
+    }
+}
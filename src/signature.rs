@@ -0,0 +1,66 @@
+//! Verification of the detached Ed25519 signatures, see
+//! [`FileEnv::verify_signatures`](crate::FileEnv::verify_signatures).
+
+use ed25519_dalek::pkcs8::DecodePublicKey;
+pub(crate) use ed25519_dalek::VerifyingKey as PublicKey;
+
+/// Parses the public key `pem`, in the PEM `PUBLIC KEY` format, e.g. generated with
+/// `openssl pkey -pubout`.
+///
+/// The error messages never quote the input.
+pub(crate) fn parse_public_key(pem: &str) -> Result<PublicKey, String> {
+    PublicKey::from_public_key_pem(pem.trim()).map_err(|e| {
+        format!(
+            "invalid Ed25519 public key, expected a PEM `PUBLIC KEY`: {}",
+            e
+        )
+    })
+}
+
+/// Decodes the signature `contents`: either the raw 64 bytes, or their base64 encoding.
+pub(crate) fn parse_signature(contents: &[u8]) -> Option<ed25519_dalek::Signature> {
+    let bytes: [u8; 64] = match contents.try_into() {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            let text = std::str::from_utf8(contents).ok()?;
+            crate::base64::decode(text.trim().trim_end_matches('='))?
+                .try_into()
+                .ok()?
+        }
+    };
+    Some(ed25519_dalek::Signature::from_bytes(&bytes))
+}
+
+/// Whether `signature` is a valid signature of `message` by `key`.
+pub(crate) fn verify(
+    key: &PublicKey,
+    message: &[u8],
+    signature: &ed25519_dalek::Signature,
+) -> bool {
+    key.verify_strict(message, signature).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing() {
+        let pem = "-----BEGIN PUBLIC KEY-----\n\
+                   MCowBQYDK2VwAyEA11qYAYKxCrfVS/7TyWQHOg7hcvPapiMlrwIaaPcHURo=\n\
+                   -----END PUBLIC KEY-----\n";
+        let key = parse_public_key(pem).unwrap();
+        assert_eq!(key.as_bytes()[..4], [0xd7, 0x5a, 0x98, 0x01],);
+        assert!(parse_public_key("d75a980182b10ab7").is_err());
+        assert_eq!(
+            parse_signature(&[7; 64]).map(|s| s.to_bytes()),
+            Some([7; 64])
+        );
+        let base64 = "B".repeat(85) + "w==\n";
+        assert_eq!(
+            parse_signature(base64.as_bytes()).map(|s| s.to_bytes()[0]),
+            Some(0x04)
+        );
+        assert!(parse_signature(b"AAAA").is_none());
+    }
+}
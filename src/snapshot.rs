@@ -0,0 +1,112 @@
+//! Detection of the rotation of the files, see [`FileEnv::snapshot`](crate::FileEnv::snapshot).
+
+use sha2::Digest;
+use std::io::Read;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// The files read by a [`FileEnv`](crate::FileEnv) at some point, to check later whether they
+/// changed with [`FileEnv::has_changed`](crate::FileEnv::has_changed).
+///
+/// The hashes of the contents are not shown by [`Debug`], since they could be used to guess
+/// short secrets.
+#[derive(Clone)]
+pub struct Snapshot {
+    pub(crate) files: Vec<FileSnapshot>,
+}
+
+impl std::fmt::Debug for Snapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.files.iter().map(|file| (&file.key, &file.path)))
+            .finish()
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct FileSnapshot {
+    key: String,
+    path: String,
+    state: FileState,
+    hash: Option<[u8; 32]>,
+}
+
+impl FileSnapshot {
+    /// Captures the file at `path`, pointed to by the env variable `key`.
+    pub(crate) fn new(key: String, path: String) -> Self {
+        let state = file_state(&path);
+        let hash = hash(&path);
+        Self {
+            key,
+            path,
+            state,
+            hash,
+        }
+    }
+
+    /// Whether the file changed. The contents are hashed again only when its size or
+    /// modification time changed, e.g. not when it was written again with the same contents.
+    pub(crate) fn has_changed(&self) -> bool {
+        let state = file_state(&self.path);
+        match (&self.state.1, &state.1) {
+            _ if state == self.state => false,
+            (Some((_, before)), Some((_, after))) if before == after => {
+                hash(&self.path) != self.hash
+            }
+            _ => true,
+        }
+    }
+}
+
+/// The state of a file: the file it resolves to, and its modification time and size, if it
+/// exists.
+pub(crate) type FileState = (Option<PathBuf>, Option<(SystemTime, u64)>);
+
+pub(crate) fn file_state(path: &str) -> FileState {
+    let metadata = std::fs::metadata(path)
+        .ok()
+        .and_then(|metadata| Some((metadata.modified().ok()?, metadata.len())));
+    (std::fs::canonicalize(path).ok(), metadata)
+}
+
+/// The SHA-256 hash of the contents of the regular file at `path`, if it can be read. The other
+/// files, e.g. a FIFO, are not read.
+fn hash(path: &str) -> Option<[u8; 32]> {
+    let mut file = std::fs::File::open(path).ok()?;
+    if !file.metadata().ok()?.is_file() {
+        return None;
+    }
+    let mut contents = Vec::new();
+    let read = file.read_to_end(&mut contents);
+    let hash = sha2::Sha256::digest(&contents).into();
+    crate::wipe::wipe(&mut contents);
+    read.ok().map(|_| hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes() {
+        let dir =
+            std::env::temp_dir().join(format!("figment_snapshot_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("secret").to_string_lossy().into_owned();
+        let missing = FileSnapshot::new("secret_file".to_string(), path.clone());
+        assert!(!missing.has_changed());
+        std::fs::write(&path, "a").unwrap();
+        assert!(missing.has_changed());
+        let created = FileSnapshot::new("secret_file".to_string(), path.clone());
+        assert!(!created.has_changed());
+        std::fs::write(&path, "a").unwrap();
+        assert!(!created.has_changed());
+        // The same size, with another modification time.
+        std::fs::write(&path, "b").unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH).unwrap();
+        assert!(created.has_changed());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(created.has_changed());
+    }
+}
@@ -0,0 +1,86 @@
+//! Decryption of the files encrypted with SOPS, detected from their contents.
+//!
+//! The decryption is delegated to the `sops` command-line tool, which must be available in the
+//! `PATH`. The decrypted document is requested as JSON and converted to a figment value.
+
+use figment::value::Value;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+
+/// Whether `contents` is a JSON or YAML document encrypted with SOPS, i.e. with a top-level
+/// `sops` metadata entry and encrypted values.
+pub(crate) fn is_encrypted(contents: &str) -> bool {
+    let has_metadata = if is_json(contents) {
+        contents.contains("\"sops\"")
+    } else {
+        contents.lines().any(|line| line.starts_with("sops:"))
+    };
+    has_metadata && contents.contains("ENC[AES256_GCM,")
+}
+
+fn is_json(contents: &str) -> bool {
+    contents.trim_start().starts_with('{')
+}
+
+/// Decrypts the SOPS document `contents` with the `sops` tool, passing it the extra environment
+/// variables `env` (e.g. `SOPS_AGE_KEY_FILE`), and returns the decrypted document as a value.
+pub(crate) fn decrypt(contents: &str, env: &[(String, String)]) -> Result<Value, String> {
+    let input_type = if is_json(contents) { "json" } else { "yaml" };
+    let mut child = Command::new("sops")
+        .args([
+            "--decrypt",
+            "--input-type",
+            input_type,
+            "--output-type",
+            "json",
+        ])
+        .arg("/dev/stdin")
+        .envs(env.iter().map(|(name, value)| (name, value)))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("could not run `sops`: {}", e))?;
+    let mut stdin = child.stdin.take().expect("piped");
+    let mut input = contents.as_bytes().to_vec();
+    // Write from another thread to avoid a deadlock when the output fills the pipe.
+    let writer = std::thread::spawn(move || {
+        let _ = stdin.write_all(&input);
+        crate::wipe::wipe(&mut input);
+    });
+    let mut output = Vec::new();
+    let read = child.stdout.take().expect("piped").read_to_end(&mut output);
+    let status = child.wait_with_output().map_err(|e| e.to_string())?;
+    writer.join().expect("writer thread panicked");
+    let output = crate::wipe::Zeroizing::new(String::from_utf8(output).map_err(|e| {
+        let mut bytes = e.into_bytes();
+        crate::wipe::wipe(&mut bytes);
+        "`sops` returned invalid UTF-8".to_string()
+    })?);
+    read.map_err(|e| e.to_string())?;
+    if !status.status.success() {
+        return Err(format!(
+            "`sops` failed to decrypt: {}",
+            String::from_utf8_lossy(&status.stderr).trim()
+        ));
+    }
+    serde_json::from_str(&output).map_err(|e| format!("invalid output from `sops`: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detection() {
+        assert!(is_encrypted(
+            r#"{"password": "ENC[AES256_GCM,data:abc,iv:def,tag:ghi,type:str]", "sops": {}}"#
+        ));
+        assert!(is_encrypted(
+            "password: ENC[AES256_GCM,data:abc,iv:def,tag:ghi,type:str]\nsops:\n    version: 3\n"
+        ));
+        assert!(!is_encrypted(r#"{"password": "hunter2", "sops": {}}"#));
+        assert!(!is_encrypted("ENC[AES256_GCM,data:abc]"));
+        assert!(!is_encrypted("hunter2"));
+    }
+}
@@ -0,0 +1,214 @@
+//! Rate limiting, concurrency limiting and circuit breaking of the reads from the secret
+//! stores, see [`FileEnv::remote_rate_limit`](crate::FileEnv::remote_rate_limit).
+
+use std::collections::HashMap;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// The limits of the reads, shared by the clones of a provider.
+#[derive(Default)]
+pub(crate) struct Throttle {
+    /// The minimum interval between the starts of two reads.
+    pub(crate) min_interval: Option<Duration>,
+    pub(crate) max_concurrent: Option<usize>,
+    /// The number of consecutive errors opening the circuit of a store, and for how long.
+    pub(crate) breaker: Option<(u32, Duration)>,
+    state: Mutex<State>,
+    released: Condvar,
+}
+
+impl std::fmt::Debug for Throttle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Throttle")
+            .field("min_interval", &self.min_interval)
+            .field("max_concurrent", &self.max_concurrent)
+            .field("breaker", &self.breaker)
+            .finish()
+    }
+}
+
+impl Clone for Throttle {
+    /// Copies the configuration, with a fresh state.
+    fn clone(&self) -> Self {
+        Self {
+            min_interval: self.min_interval,
+            max_concurrent: self.max_concurrent,
+            breaker: self.breaker,
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    /// The start of the last read, or of the next one already scheduled.
+    last_start: Option<Instant>,
+    in_flight: usize,
+    /// By store: the consecutive errors, and until when the circuit is open.
+    circuits: HashMap<String, (u32, Option<Instant>)>,
+}
+
+/// The permission to read from a store, released when dropped.
+pub(crate) struct Permit<'a> {
+    throttle: &'a Throttle,
+    store: &'a str,
+}
+
+impl Throttle {
+    fn is_active(&self) -> bool {
+        self.min_interval.is_some() || self.max_concurrent.is_some() || self.breaker.is_some()
+    }
+
+    /// Waits until a read from `store` is allowed, or fails right away if its circuit is open.
+    pub(crate) fn acquire<'a>(&'a self, store: &'a str) -> Result<Permit<'a>, String> {
+        if !self.is_active() {
+            return Ok(Permit {
+                throttle: self,
+                store,
+            });
+        }
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((max_errors, _)) = self.breaker {
+            if let Some(&(_, Some(open_until))) = state.circuits.get(store) {
+                let now = Instant::now();
+                if now < open_until {
+                    return Err(format!(
+                        "failing fast after {} consecutive errors reading from `{}:`, for \
+                         another {:?}",
+                        max_errors,
+                        store,
+                        Duration::from_secs((open_until - now).as_secs_f64().ceil() as u64)
+                    ));
+                }
+                // Let a single read through: another error opens the circuit again.
+                let errors = max_errors.saturating_sub(1);
+                state.circuits.insert(store.to_string(), (errors, None));
+            }
+        }
+        if let Some(max) = self.max_concurrent {
+            while state.in_flight >= max {
+                state = self.released.wait(state).unwrap_or_else(|e| e.into_inner());
+            }
+        }
+        state.in_flight += 1;
+        let wait = self.min_interval.and_then(|interval| {
+            let now = Instant::now();
+            let start = state
+                .last_start
+                .map_or(now, |last| (last + interval).max(now));
+            state.last_start = Some(start);
+            start.checked_duration_since(now)
+        });
+        drop(state);
+        if let Some(wait) = wait {
+            std::thread::sleep(wait);
+        }
+        Ok(Permit {
+            throttle: self,
+            store,
+        })
+    }
+}
+
+impl Permit<'_> {
+    /// Records the outcome of the read, for the circuit breaker.
+    pub(crate) fn finish(self, failed: bool) {
+        let Some((max_errors, cooldown)) = self.throttle.breaker else {
+            return;
+        };
+        let mut state = self
+            .throttle
+            .state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let circuit = state.circuits.entry(self.store.to_string()).or_default();
+        match failed {
+            true => {
+                circuit.0 += 1;
+                if circuit.0 >= max_errors {
+                    circuit.1 = Some(Instant::now() + cooldown);
+                }
+            }
+            false => *circuit = (0, None),
+        }
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if !self.throttle.is_active() {
+            return;
+        }
+        let mut state = self
+            .throttle
+            .state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        state.in_flight -= 1;
+        self.throttle.released.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limit() {
+        let throttle = Throttle {
+            min_interval: Some(Duration::from_millis(50)),
+            ..Throttle::default()
+        };
+        let start = Instant::now();
+        for _ in 0..3 {
+            throttle.acquire("vault").unwrap().finish(false);
+        }
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn circuit_breaker() {
+        let throttle = Throttle {
+            breaker: Some((2, Duration::from_millis(100))),
+            ..Throttle::default()
+        };
+        throttle.acquire("vault").unwrap().finish(true);
+        throttle.acquire("vault").unwrap().finish(true);
+        let error = throttle.acquire("vault").err().unwrap();
+        assert!(
+            error.starts_with("failing fast after 2 consecutive errors reading from `vault:`"),
+            "{}",
+            error
+        );
+        // The other stores are not affected.
+        throttle.acquire("keyring").unwrap().finish(false);
+        std::thread::sleep(Duration::from_millis(100));
+        // Half-open: a single error opens the circuit again.
+        throttle.acquire("vault").unwrap().finish(true);
+        assert!(throttle.acquire("vault").is_err());
+        std::thread::sleep(Duration::from_millis(100));
+        throttle.acquire("vault").unwrap().finish(false);
+        throttle.acquire("vault").unwrap().finish(true);
+        assert!(throttle.acquire("vault").is_ok());
+    }
+
+    #[test]
+    fn concurrency_limit() {
+        let throttle = std::sync::Arc::new(Throttle {
+            max_concurrent: Some(1),
+            ..Throttle::default()
+        });
+        let permit = throttle.acquire("vault").unwrap();
+        let other = {
+            let throttle = throttle.clone();
+            std::thread::spawn(move || {
+                let start = Instant::now();
+                throttle.acquire("vault").unwrap().finish(false);
+                start.elapsed()
+            })
+        };
+        std::thread::sleep(Duration::from_millis(50));
+        drop(permit);
+        assert!(other.join().unwrap() >= Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,138 @@
+//! Reading the secrets of HashiCorp Vault with its HTTP API, for `vault://` references.
+
+use figment::value::Value;
+use std::path::PathBuf;
+
+/// The configuration of the Vault client, see
+/// [`FileEnv::vault_addr`](crate::FileEnv::vault_addr).
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Options {
+    /// The address of the server, `VAULT_ADDR` by default.
+    pub(crate) addr: Option<String>,
+    /// The file holding the token, used when `VAULT_TOKEN` is not set.
+    pub(crate) token_file: Option<PathBuf>,
+}
+
+fn error(kind: std::io::ErrorKind, message: String) -> std::io::Error {
+    std::io::Error::new(kind, message)
+}
+
+/// Reads the `reference`, `<path>#<field>` (e.g. `secret/data/myapp#db_password`), from the
+/// KV secrets engine, version 1 or 2.
+///
+/// The requests are made with the [`http`](crate::http) options, plus the token.
+pub(crate) fn read(
+    reference: &str,
+    options: &Options,
+    http: &crate::http::Options,
+) -> std::io::Result<Vec<u8>> {
+    use std::io::ErrorKind;
+    let (path, field) = reference.split_once('#').ok_or_else(|| {
+        error(
+            ErrorKind::InvalidInput,
+            format!(
+                "the Vault reference `{}` has no field, e.g. `vault://{}#password`",
+                reference, reference
+            ),
+        )
+    })?;
+    let addr = match &options.addr {
+        Some(addr) => addr.clone(),
+        None => std::env::var("VAULT_ADDR").map_err(|_| {
+            error(
+                ErrorKind::NotFound,
+                "no Vault address: set `VAULT_ADDR`".to_string(),
+            )
+        })?,
+    };
+    let token = match (std::env::var("VAULT_TOKEN"), &options.token_file) {
+        (Ok(token), _) => token,
+        (Err(_), Some(token_file)) => std::fs::read_to_string(token_file).map_err(|e| {
+            error(
+                e.kind(),
+                format!(
+                    "could not read the Vault token file `{}`: {}",
+                    token_file.display(),
+                    e
+                ),
+            )
+        })?,
+        (Err(_), None) => {
+            return Err(error(
+                ErrorKind::NotFound,
+                "no Vault token: set `VAULT_TOKEN`".to_string(),
+            ))
+        }
+    };
+    let mut http = http.clone();
+    http.headers
+        .push(("X-Vault-Token".to_string(), token.trim().to_string()));
+    if let Ok(namespace) = std::env::var("VAULT_NAMESPACE") {
+        http.headers
+            .push(("X-Vault-Namespace".to_string(), namespace));
+    }
+    let url = format!(
+        "{}/v1/{}",
+        addr.trim_end_matches('/'),
+        path.trim_start_matches('/')
+    );
+    let response = crate::wipe::Zeroizing::new(
+        String::from_utf8(crate::http::read(&url, &http, None)?).map_err(|e| {
+            let mut bytes = e.into_bytes();
+            crate::wipe::wipe(&mut bytes);
+            error(
+                ErrorKind::InvalidData,
+                "Vault returned invalid UTF-8".to_string(),
+            )
+        })?,
+    );
+    let response = serde_json::from_str::<Value>(&response).map_err(|e| {
+        error(
+            ErrorKind::InvalidData,
+            format!("invalid response from Vault: {}", e),
+        )
+    })?;
+    field_value(&response, field)
+        .map(|value| value.as_bytes().to_vec())
+        .ok_or_else(|| {
+            error(
+                ErrorKind::NotFound,
+                format!(
+                    "the Vault secret `{}` has no string field `{}`",
+                    path, field
+                ),
+            )
+        })
+}
+
+/// The string `field` of the secret in `response`: in `data.data` for the version 2 of the KV
+/// secrets engine, or in `data` for the version 1.
+fn field_value<'a>(response: &'a Value, field: &str) -> Option<&'a str> {
+    let data = response.as_dict()?.get("data")?.as_dict()?;
+    let secret = match (data.get("data"), data.get("metadata")) {
+        (Some(Value::Dict(_, secret)), Some(_)) => secret,
+        _ => data,
+    };
+    secret.get(field)?.as_str()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fields() {
+        let v2 = serde_json::from_str::<Value>(
+            r#"{"data": {"data": {"db_password": "hunter2"}, "metadata": {"version": 3}}}"#,
+        )
+        .unwrap();
+        assert_eq!(field_value(&v2, "db_password"), Some("hunter2"));
+        assert_eq!(field_value(&v2, "metadata"), None);
+        let v1 =
+            serde_json::from_str::<Value>(r#"{"data": {"db_password": "hunter2", "port": 5432}}"#)
+                .unwrap();
+        assert_eq!(field_value(&v1, "db_password"), Some("hunter2"));
+        assert_eq!(field_value(&v1, "port"), None);
+        assert_eq!(field_value(&v1, "other"), None);
+    }
+}
@@ -0,0 +1,279 @@
+//! Reloading the configuration when the files change, see [`FileEnvWatcher`].
+
+use crate::snapshot::{file_state, FileState};
+use crate::FileEnv;
+use figment::error::Kind;
+use figment::Figment;
+use notify::{RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+
+/// How long the events of a change are gathered before reading the files again, e.g. the
+/// several events of a file being replaced.
+const SETTLE_TIME: Duration = Duration::from_millis(50);
+
+/// Watches the files read by a [`FileEnv`], and reads them again when one of them changes, for
+/// the rotation of the secrets without restarting the application.
+///
+/// The directories of the files are watched with the notification API of the platform (see
+/// [`notify`]), so that a file is seen when it is created, removed or modified, or when its path
+/// points to another file, e.g. when Kubernetes swaps the symbolic link of a mounted secret. The
+/// files are those of the last read, including the ones that could not be read.
+///
+/// A [`FileEnv`] is not [`Send`], so the watcher holds a function building it, called on the
+/// thread for each read. The callback gets a [`Figment`] of the provider: the errors of the
+/// read are reported when extracting the configuration.
+///
+/// ```rust
+/// use figment::providers::Env;
+/// use figment_file_env_provider::{FileEnv, FileEnvWatcher};
+///
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     db_password: String,
+/// }
+///
+/// let (sender, receiver) = std::sync::mpsc::channel();
+/// let watcher = FileEnvWatcher::spawn(
+///     || FileEnv::from_env(Env::prefixed("APP_")),
+///     move |figment| {
+///         let _ = sender.send(figment.extract::<Config>());
+///     },
+/// )?;
+/// // Receive the new configurations from `receiver`.
+/// # drop(receiver);
+/// watcher.stop();
+/// # Ok::<(), figment::Error>(())
+/// ```
+///
+/// See [`FileEnvWatcher::subscribe`] to react to the changes of each file.
+#[derive(Debug)]
+pub struct FileEnvWatcher {
+    stop: Option<mpsc::Sender<Message>>,
+    thread: Option<std::thread::JoinHandle<()>>,
+    subscribers: Subscribers,
+}
+
+type Subscribers = Arc<Mutex<Vec<mpsc::Sender<ChangeEvent>>>>;
+
+/// A message to the thread of a [`FileEnvWatcher`]: an event of a watched directory, or `None`
+/// to stop.
+type Message = Option<notify::Result<notify::Event>>;
+
+/// A change of a file watched by a [`FileEnvWatcher`], see [`FileEnvWatcher::subscribe`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChangeEvent {
+    /// The env variable pointing to the file, e.g. `db_password_file`.
+    pub key: String,
+    /// The path of the file.
+    pub path: String,
+    /// How the file changed.
+    pub kind: ChangeKind,
+}
+
+/// How a file changed, see [`ChangeEvent`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ChangeKind {
+    /// The file did not exist.
+    Created,
+    /// The contents of the file changed, or its path points to another file.
+    Modified,
+    /// The file no longer exists.
+    Removed,
+}
+
+impl FileEnvWatcher {
+    /// Starts watching the files of the provider returned by `build`, and calls `on_change`
+    /// with the provider read again after each change.
+    ///
+    /// The files are read once when starting, to find the paths to watch, without calling
+    /// `on_change`. Fails if the notification API is not available.
+    // Same error type as `Provider::data`.
+    #[allow(clippy::result_large_err)]
+    pub fn spawn<B, F>(build: B, mut on_change: F) -> Result<Self, figment::Error>
+    where
+        B: Fn() -> FileEnv + Send + 'static,
+        F: FnMut(Figment) + Send + 'static,
+    {
+        let (stop, messages) = mpsc::channel();
+        let events = stop.clone();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = events.send(Some(event));
+        })
+        .map_err(|e| Kind::Message(format!("Could not watch the files: {}", e)))?;
+        let subscribers = Subscribers::default();
+        let thread_subscribers = subscribers.clone();
+        let thread = std::thread::spawn(move || {
+            let read = || {
+                let file_env = build();
+                let figment = Figment::from(&file_env);
+                (figment, file_env.read_paths())
+            };
+            let (_, mut files) = read();
+            let mut state = snapshot(&files);
+            let mut dirs = HashSet::new();
+            watch_dirs(&mut watcher, &mut dirs, &files);
+            while let Ok(Some(_)) = messages.recv() {
+                loop {
+                    match messages.recv_timeout(SETTLE_TIME) {
+                        Ok(Some(_)) => continue,
+                        Err(mpsc::RecvTimeoutError::Timeout) => break,
+                        Ok(None) | Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+                let current = snapshot(&files);
+                if current == state {
+                    continue;
+                }
+                let events: Vec<_> = files
+                    .iter()
+                    .zip(state.iter().zip(&current))
+                    .filter_map(|((key, path), (before, after))| {
+                        let kind = match (&before.1, &after.1) {
+                            _ if before == after => return None,
+                            (None, Some(_)) => ChangeKind::Created,
+                            (Some(_), None) => ChangeKind::Removed,
+                            _ => ChangeKind::Modified,
+                        };
+                        Some(ChangeEvent {
+                            key: key.clone(),
+                            path: path.clone(),
+                            kind,
+                        })
+                    })
+                    .collect();
+                thread_subscribers
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .retain(|subscriber| {
+                        events
+                            .iter()
+                            .all(|event| subscriber.send(event.clone()).is_ok())
+                    });
+                let (figment, read_files) = read();
+                files = read_files;
+                state = snapshot(&files);
+                watch_dirs(&mut watcher, &mut dirs, &files);
+                on_change(figment);
+            }
+        });
+        Ok(Self {
+            stop: Some(stop),
+            thread: Some(thread),
+            subscribers,
+        })
+    }
+
+    /// Receives an event for each watched file that changes, to react to the changes of some
+    /// keys only, e.g. to rebuild the database pool when `db_password_file` changes. The events
+    /// of a change are sent before calling the callback of [`FileEnvWatcher::spawn`].
+    ///
+    /// Dropping the receiver unsubscribes it.
+    ///
+    /// ```rust
+    /// # use figment::providers::Env;
+    /// # use figment_file_env_provider::{FileEnv, FileEnvWatcher};
+    /// let watcher = FileEnvWatcher::spawn(|| FileEnv::from_env(Env::prefixed("APP_")), |_| {})?;
+    /// let changes = watcher.subscribe();
+    /// std::thread::spawn(move || {
+    ///     for change in changes {
+    ///         if change.key == "db_password_file" {
+    ///             // Rebuild the database pool.
+    ///         }
+    ///     }
+    /// });
+    /// # Ok::<(), figment::Error>(())
+    /// ```
+    pub fn subscribe(&self) -> mpsc::Receiver<ChangeEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(sender);
+        receiver
+    }
+
+    /// Stops watching, and waits for the thread, e.g. for a read in progress.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(None);
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for FileEnvWatcher {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn snapshot(files: &[(String, String)]) -> Vec<FileState> {
+    files.iter().map(|(_, path)| file_state(path)).collect()
+}
+
+/// Watches the directories of the `files` and of the files they resolve to, in place of the
+/// `dirs` watched so far: a file replaced or created is only seen from its directory. A missing
+/// directory is watched from its nearest existing parent, until the files are read again.
+fn watch_dirs(watcher: &mut impl Watcher, dirs: &mut HashSet<PathBuf>, files: &[(String, String)]) {
+    let wanted: HashSet<_> = files
+        .iter()
+        .flat_map(|(_, path)| [Some(PathBuf::from(path)), std::fs::canonicalize(path).ok()])
+        .flatten()
+        .filter_map(|path| existing_dir(&path))
+        .collect();
+    for dir in dirs.difference(&wanted) {
+        let _ = watcher.unwatch(dir);
+    }
+    let watched = wanted
+        .into_iter()
+        .filter(|dir| dirs.contains(dir) || watcher.watch(dir, RecursiveMode::NonRecursive).is_ok())
+        .collect();
+    *dirs = watched;
+}
+
+/// The nearest existing directory containing `path`.
+fn existing_dir(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .map(|dir| match dir.as_os_str().is_empty() {
+            true => Path::new("."),
+            false => dir,
+        })
+        .find(|dir| dir.is_dir())
+        .map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_states() {
+        let dir = std::env::temp_dir().join(format!("figment_watch_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("secret").to_string_lossy().into_owned();
+        let paths = [("secret_file".to_string(), path.clone())];
+        let missing = snapshot(&paths);
+        assert_eq!(missing, [(None, None)]);
+        std::fs::write(&path, "a").unwrap();
+        let created = snapshot(&paths);
+        assert_ne!(created, missing);
+        std::fs::write(&path, "ab").unwrap();
+        assert_ne!(snapshot(&paths), created);
+        assert_eq!(existing_dir(Path::new(&path)), Some(dir.clone()));
+        assert_eq!(existing_dir(&dir.join("missing/secret")), Some(dir.clone()));
+        assert_eq!(existing_dir(Path::new("secret")), Some(PathBuf::from(".")));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,61 @@
+//! Wiping of the buffers holding file contents, with the `zeroize` feature.
+//!
+//! Without the feature, the buffers are freed as usual.
+
+#[cfg(not(feature = "zeroize"))]
+use std::ops::{Deref, DerefMut};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+#[cfg(feature = "zeroize")]
+pub(crate) use zeroize::Zeroizing;
+
+/// Overwrites `bytes`, including their spare capacity, with zeros, and empties them, with the
+/// `zeroize` feature.
+pub(crate) fn wipe(bytes: &mut Vec<u8>) {
+    #[cfg(feature = "zeroize")]
+    bytes.zeroize();
+    #[cfg(not(feature = "zeroize"))]
+    let _ = bytes;
+}
+
+/// A value holding file contents. Without the `zeroize` feature, it is dropped as usual.
+#[cfg(not(feature = "zeroize"))]
+pub(crate) struct Zeroizing<T>(T);
+
+#[cfg(not(feature = "zeroize"))]
+impl<T> Zeroizing<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Self(value)
+    }
+}
+
+#[cfg(not(feature = "zeroize"))]
+impl<T> Deref for Zeroizing<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+#[cfg(not(feature = "zeroize"))]
+impl<T> DerefMut for Zeroizing<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+#[cfg(all(test, feature = "zeroize"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wipes() {
+        let mut bytes = Vec::with_capacity(16);
+        bytes.extend_from_slice(b"hunter2");
+        wipe(&mut bytes);
+        assert!(bytes.is_empty());
+        let contents = Zeroizing::new("hunter2".to_string());
+        assert_eq!(contents.as_str(), "hunter2");
+    }
+}